@@ -0,0 +1,120 @@
+use std::hash::Hash;
+
+/// Computes digests for the leaves and internal nodes of a `MerkleTree`.
+///
+/// A `Hasher` owns the digest type (`Digest`) it produces. Leaves are
+/// hashed first via `hash_leaf`, then pairs of child digests are folded
+/// upward into their parent's digest via `hash_nodes`, bottom-up, until a
+/// single root digest remains.
+pub trait Hasher {
+    /// The digest type produced for every node in the tree.
+    type Digest: Clone + PartialEq;
+
+    /// Hashes a leaf's value into a digest.
+    fn hash_leaf<T: Hash>(value: &T) -> Self::Digest;
+
+    /// Combines a left and right child digest into their parent's digest.
+    fn hash_nodes(left: &Self::Digest, right: &Self::Digest) -> Self::Digest;
+}
+
+/// A cheap `Hasher` built on `std::hash::Hasher`, useful for tests and any
+/// caller that doesn't need cryptographic guarantees.
+pub struct U64Hasher;
+
+impl Hasher for U64Hasher {
+    type Digest = u64;
+
+    fn hash_leaf<T: Hash>(value: &T) -> Self::Digest {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::Hasher as _;
+
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn hash_nodes(left: &Self::Digest, right: &Self::Digest) -> Self::Digest {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::Hasher as _;
+
+        let mut hasher = DefaultHasher::new();
+        left.hash(&mut hasher);
+        right.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+/// A SHA-256 backed `Hasher` producing 32-byte digests.
+#[cfg(feature = "sha2")]
+pub struct Sha256Hasher;
+
+/// A `std::hash::Hasher` that just records every byte it's fed, so feeding
+/// a value through `Hash::hash` recovers its full byte representation
+/// instead of collapsing it down to a 64-bit fingerprint first.
+#[cfg(feature = "sha2")]
+#[derive(Default)]
+struct ByteCollector(Vec<u8>);
+
+#[cfg(feature = "sha2")]
+impl std::hash::Hasher for ByteCollector {
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.extend_from_slice(bytes);
+    }
+
+    fn finish(&self) -> u64 {
+        // Never consulted: `Sha256Hasher` reads `self.0` directly instead.
+        0
+    }
+}
+
+#[cfg(feature = "sha2")]
+impl Hasher for Sha256Hasher {
+    type Digest = [u8; 32];
+
+    fn hash_leaf<T: Hash>(value: &T) -> Self::Digest {
+        use sha2::{Digest as _, Sha256};
+
+        // `T` is arbitrary, so first recover its full byte representation
+        // via `std::hash::Hash` before feeding it to SHA-256 — collapsing
+        // through a 64-bit fingerprint first would throw away SHA-256's
+        // collision resistance.
+        let mut collector = ByteCollector::default();
+        value.hash(&mut collector);
+
+        let mut sha = Sha256::new();
+        sha.update(&collector.0);
+        sha.finalize().into()
+    }
+
+    fn hash_nodes(left: &Self::Digest, right: &Self::Digest) -> Self::Digest {
+        use sha2::{Digest as _, Sha256};
+
+        let mut sha = Sha256::new();
+        sha.update(left);
+        sha.update(right);
+        sha.finalize().into()
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "sha2")]
+mod sha256_hasher {
+    use super::*;
+    use crate::merkle_tree::MerkleTree;
+
+    #[test]
+    fn root_is_distinct_and_deterministic() {
+        let input = vec![1, 2, 3, 4];
+
+        let first: MerkleTree<&i32, Sha256Hasher> = MerkleTree::new(&input);
+        let second: MerkleTree<&i32, Sha256Hasher> = MerkleTree::new(&input);
+
+        let root = first.root_hash().expect("non-empty tree has a root");
+        assert_eq!(Some(root), second.root_hash());
+        assert_ne!(*root, [0u8; 32]);
+
+        let other_input = vec![1, 2, 3, 5];
+        let other: MerkleTree<&i32, Sha256Hasher> = MerkleTree::new(&other_input);
+        assert_ne!(Some(root), other.root_hash());
+    }
+}
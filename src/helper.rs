@@ -1,10 +1,23 @@
-/// Returns the size of the whole tree given, the number of leafs.
+/// Returns an upper bound on the size of the whole tree given the number of
+/// leafs, used to size the tree's node `Vec` up front.
+///
+/// This assumes every row above the leafs materializes `ceil(row_len / 2)`
+/// new nodes, summing `leafs + ceil(leafs / 2) + ceil(ceil(leafs / 2) / 2)
+/// + ...` down to the root. That's exact for a perfect power-of-two leaf
+/// count, but an overestimate whenever a row carries a lone trailing node
+/// up unpaired (see `MerkleTree::build_parents`): the carried node is
+/// reused rather than duplicated, so it doesn't actually cost a new
+/// allocation every row it passes through. Overcounting only costs some
+/// unused `Vec` capacity, so it's fine for a capacity hint. A single leaf
+/// (or none) is the one case where it falls below the real size: the
+/// `Vec` also holds a separate root entry, one more than this returns.
 pub(crate) fn calculate_tree_size(leafs: u64) -> u64 {
-    if leafs == 1 {
+    if leafs <= 1 {
         return leafs;
     }
 
-    return leafs + calculate_tree_size(leafs / 2);
+    let parent_row = leafs.div_ceil(2);
+    leafs + calculate_tree_size(parent_row)
 }
 
 #[cfg(test)]
@@ -31,4 +44,16 @@ mod test {
 
         assert_eq!(node_count, 15);
     }
+
+    #[test]
+    fn node_calculation_odd_rows() {
+        // These are the capacity-hint upper bound, not the real node
+        // count: each sums leafs + ceil(leafs/2) + ... down to the root,
+        // as if every carried node were freshly allocated at each row it
+        // passes through rather than reused.
+        assert_eq!(calculate_tree_size(3), 6); // 3 + 2 + 1
+        assert_eq!(calculate_tree_size(5), 11); // 5 + 3 + 2 + 1
+        assert_eq!(calculate_tree_size(6), 12); // 6 + 3 + 2 + 1
+        assert_eq!(calculate_tree_size(7), 14); // 7 + 4 + 2 + 1
+    }
 }
@@ -0,0 +1,291 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+use crate::hasher::Hasher;
+
+/// How long an appended leaf's digest should be retained for, mirroring
+/// the retention model of append-only transparency logs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Retention<Id> {
+    /// May be dropped as soon as it's no longer needed to fold carries
+    /// upward; this is the default for a plain streaming append.
+    Ephemeral,
+    /// Kept around so the leaf's digest survives a `rewind` to any
+    /// checkpoint recorded after it, for later witness computation. A
+    /// `rewind` to a checkpoint recorded before it discards it along with
+    /// the other later appends.
+    Marked,
+    /// Like `Ephemeral`, but also snapshots the builder's frontier under
+    /// `id` so `rewind` can later restore exactly this state.
+    Checkpoint { id: Id },
+}
+
+/// A snapshot of the builder's frontier, taken at a `Retention::Checkpoint`.
+struct Snapshot<H: Hasher> {
+    levels: Vec<Option<H::Digest>>,
+    leaf_count: u64,
+    marked_len: usize,
+}
+
+/// Builds a Merkle root incrementally, appending leaves in batches without
+/// ever holding the full tree in memory.
+///
+/// Only one pending digest is kept per level (a "carry": a left child
+/// still waiting for its right sibling), so the state between batches is
+/// `O(log n)` regardless of how many leaves have been appended so far.
+/// This mirrors the running-hash approach used by append-only transparency
+/// logs: appending a leaf is like incrementing a binary counter, with each
+/// carry fold combining the waiting digest with the new one.
+///
+/// Tagging an append with [`Retention::Marked`] or [`Retention::Checkpoint`]
+/// lets the builder back out of a speculative run of appends (e.g. a
+/// reorged block) via [`IncrementalBuilder::rewind`], which a flat `Vec`
+/// rebuild can't do cheaply. `Id` is the type used to name checkpoints,
+/// defaulting to `u64`.
+pub struct IncrementalBuilder<T, H: Hasher, Id = u64> {
+    /// `levels[i]` is the carry digest waiting at level `i` (0 = leaves),
+    /// if a pairing at that level hasn't arrived yet.
+    levels: Vec<Option<H::Digest>>,
+    /// Number of leaves appended so far; a `rewind` restores it to the
+    /// count recorded at the checkpoint.
+    leaf_count: u64,
+    /// Digests of `Marked` leaves, in append order, each paired with the
+    /// position it was appended at.
+    marked: Vec<(u64, H::Digest)>,
+    checkpoints: HashMap<Id, Snapshot<H>>,
+    _value: PhantomData<T>,
+}
+
+impl<T: Hash, H: Hasher, Id: Eq + Hash> Default for IncrementalBuilder<T, H, Id> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Hash, H: Hasher, Id: Eq + Hash> IncrementalBuilder<T, H, Id> {
+    /// Creates a builder with no leaves appended yet.
+    pub fn new() -> Self {
+        IncrementalBuilder {
+            levels: Vec::new(),
+            leaf_count: 0,
+            marked: Vec::new(),
+            checkpoints: HashMap::new(),
+            _value: PhantomData,
+        }
+    }
+
+    /// Hashes and appends `leaves` as `Retention::Ephemeral`, folding
+    /// carries upward wherever a pairing completes. Leaves are absorbed
+    /// one at a time, so batches of any size are supported.
+    pub fn append_batch(&mut self, leaves: &[T]) {
+        for leaf in leaves {
+            self.fold_in(H::hash_leaf(leaf));
+            self.leaf_count += 1;
+        }
+    }
+
+    /// Hashes and appends a single leaf under the given `retention`.
+    pub fn append(&mut self, leaf: &T, retention: Retention<Id>) {
+        let digest = H::hash_leaf(leaf);
+        let position = self.leaf_count;
+
+        if let Retention::Marked = retention {
+            self.marked.push((position, digest.clone()));
+        }
+
+        self.fold_in(digest);
+        self.leaf_count += 1;
+
+        if let Retention::Checkpoint { id } = retention {
+            self.checkpoints.insert(
+                id,
+                Snapshot {
+                    levels: self.levels.clone(),
+                    leaf_count: self.leaf_count,
+                    marked_len: self.marked.len(),
+                },
+            );
+        }
+    }
+
+    /// Folds `digest` upward through the carry levels, starting at the
+    /// leaf level.
+    fn fold_in(&mut self, mut digest: H::Digest) {
+        let mut level = 0;
+
+        loop {
+            if level == self.levels.len() {
+                self.levels.push(None);
+            }
+
+            match self.levels[level].take() {
+                Some(carry) => {
+                    // A pairing completed at this level; fold it into
+                    // the parent digest and keep carrying it upward.
+                    digest = H::hash_nodes(&carry, &digest);
+                    level += 1;
+                }
+                None => {
+                    // Nothing waiting at this level yet: stash here.
+                    self.levels[level] = Some(digest);
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Restores the builder to exactly the state it had immediately after
+    /// `checkpoint_id` was recorded, discarding any later appends and any
+    /// checkpoints recorded after it. Returns `false` if `checkpoint_id`
+    /// was never recorded.
+    pub fn rewind(&mut self, checkpoint_id: &Id) -> bool {
+        let Some(snapshot) = self.checkpoints.get(checkpoint_id) else {
+            return false;
+        };
+
+        self.levels = snapshot.levels.clone();
+        self.leaf_count = snapshot.leaf_count;
+        self.marked.truncate(snapshot.marked_len);
+
+        let restored_leaf_count = self.leaf_count;
+        self.checkpoints
+            .retain(|_, snapshot| snapshot.leaf_count <= restored_leaf_count);
+
+        true
+    }
+
+    /// Returns the retained digest of a `Marked` leaf at `position`, if
+    /// one was recorded and hasn't since been rewound past. Producing a
+    /// full authentication path for it requires the surrounding tree
+    /// structure, which this streaming builder doesn't keep; callers that
+    /// need a proof should rebuild via `MerkleTree` once the leaves it
+    /// spans are no longer speculative.
+    pub fn marked_digest(&self, position: u64) -> Option<&H::Digest> {
+        self.marked
+            .iter()
+            .find(|(marked_position, _)| *marked_position == position)
+            .map(|(_, digest)| digest)
+    }
+
+    /// Folds the remaining carries into a single root. Returns `None` if
+    /// no leaves were ever appended.
+    pub fn finalize(self) -> Option<H::Digest> {
+        let mut acc: Option<H::Digest> = None;
+
+        for carry in self.levels.into_iter() {
+            acc = match (acc, carry) {
+                (None, carry) => carry,
+                (Some(acc), None) => Some(acc),
+                (Some(acc), Some(carry)) => Some(H::hash_nodes(&carry, &acc)),
+            };
+        }
+
+        acc
+    }
+}
+
+#[cfg(test)]
+mod incremental {
+    use super::*;
+    use crate::hasher::U64Hasher;
+    use crate::merkle_tree::MerkleTree;
+
+    #[test]
+    fn empty_builder_has_no_root() {
+        let builder = IncrementalBuilder::<i32, U64Hasher>::new();
+        assert_eq!(builder.finalize(), None);
+    }
+
+    #[test]
+    fn single_batch_matches_build_parents_root() {
+        let input = vec![1, 2, 3, 4, 5, 6, 7];
+
+        let mut builder = IncrementalBuilder::<&i32, U64Hasher>::new();
+        builder.append_batch(&input.iter().collect::<Vec<_>>());
+
+        let merkle_tree: MerkleTree<&i32, U64Hasher> = MerkleTree::new(&input);
+
+        assert_eq!(builder.finalize().as_ref(), merkle_tree.root_hash());
+    }
+
+    #[test]
+    fn splitting_into_batches_gives_the_same_root() {
+        let input = vec![1, 2, 3, 4, 5, 6, 7];
+
+        let mut one_batch = IncrementalBuilder::<&i32, U64Hasher>::new();
+        one_batch.append_batch(&input.iter().collect::<Vec<_>>());
+
+        let mut many_batches = IncrementalBuilder::<&i32, U64Hasher>::new();
+        for leaf in input.iter() {
+            many_batches.append_batch(&[leaf]);
+        }
+
+        assert_eq!(one_batch.finalize(), many_batches.finalize());
+    }
+
+    #[test]
+    fn rewind_restores_root_at_checkpoint() {
+        let mut builder = IncrementalBuilder::<i32, U64Hasher, &str>::new();
+        builder.append(&1, Retention::Ephemeral);
+        builder.append(&2, Retention::Checkpoint { id: "before-reorg" });
+        let root_at_checkpoint = builder.clone_root_for_test();
+
+        // Speculative appends that will be rewound.
+        builder.append(&3, Retention::Ephemeral);
+        builder.append(&4, Retention::Ephemeral);
+        assert_ne!(builder.clone_root_for_test(), root_at_checkpoint);
+
+        assert!(builder.rewind(&"before-reorg"));
+        assert_eq!(builder.clone_root_for_test(), root_at_checkpoint);
+    }
+
+    #[test]
+    fn rewind_drops_later_checkpoints() {
+        let mut builder = IncrementalBuilder::<i32, U64Hasher, &str>::new();
+        builder.append(&1, Retention::Checkpoint { id: "a" });
+        builder.append(&2, Retention::Checkpoint { id: "b" });
+
+        assert!(builder.rewind(&"a"));
+        assert!(!builder.rewind(&"b"));
+    }
+
+    #[test]
+    fn rewind_to_unknown_checkpoint_fails_without_side_effects() {
+        let mut builder = IncrementalBuilder::<i32, U64Hasher, &str>::new();
+        builder.append(&1, Retention::Checkpoint { id: "a" });
+        let root_before = builder.clone_root_for_test();
+
+        assert!(!builder.rewind(&"missing"));
+        assert_eq!(builder.clone_root_for_test(), root_before);
+    }
+
+    #[test]
+    fn marked_leaf_survives_a_rewind_to_a_later_checkpoint() {
+        let mut builder = IncrementalBuilder::<i32, U64Hasher, &str>::new();
+        builder.append(&1, Retention::Marked);
+        let marked_digest = builder.marked_digest(0).cloned();
+        builder.append(&2, Retention::Checkpoint { id: "a" });
+        builder.append(&3, Retention::Ephemeral);
+
+        builder.rewind(&"a");
+
+        assert_eq!(builder.marked_digest(0).cloned(), marked_digest);
+    }
+
+    impl<T: Hash, H: Hasher, Id: Eq + Hash> IncrementalBuilder<T, H, Id> {
+        /// Test-only helper: clones the builder's levels to compute a
+        /// root without consuming `self`, since `finalize` is by-value.
+        fn clone_root_for_test(&self) -> Option<H::Digest> {
+            let mut acc: Option<H::Digest> = None;
+            for carry in self.levels.iter().cloned() {
+                acc = match (acc, carry) {
+                    (None, carry) => carry,
+                    (Some(acc), None) => Some(acc),
+                    (Some(acc), Some(carry)) => Some(H::hash_nodes(&carry, &acc)),
+                };
+            }
+            acc
+        }
+    }
+}
@@ -0,0 +1,8 @@
+pub mod hasher;
+pub mod helper;
+pub mod incremental;
+pub mod merkle_tree;
+
+pub use hasher::{Hasher, U64Hasher};
+pub use incremental::{IncrementalBuilder, Retention};
+pub use merkle_tree::{verify_proof, Direction, MerkleTree, Node, NodeId};
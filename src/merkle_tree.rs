@@ -1,26 +1,151 @@
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+use crate::hasher::Hasher;
 use crate::helper::calculate_tree_size;
 
 /// Index position of the Node in tree.
 pub type NodeId = usize;
 
-/// Merkle Tree structure that holds a vector of Nodes.
-pub struct MerkleTree<T> {
-    nodes: Vec<Node<T>>,
+/// Merkle Tree structure that holds a vector of Nodes. `H` is the
+/// `Hasher` used to compute leaf and parent digests.
+pub struct MerkleTree<T, H: Hasher> {
+    nodes: Vec<Node<T, H::Digest>>,
+    _hasher: PhantomData<H>,
+}
+
+impl<T, H: Hasher> MerkleTree<T, H> {
+    /// Constructor that will return a MerkleTree<T> with the root initialised
+    /// to a zero value with no nodes and leafs.
+    pub fn new_empty() -> MerkleTree<T, H> {
+        let nodes = vec![Node::new()];
+
+        MerkleTree {
+            nodes,
+            _hasher: PhantomData,
+        }
+    }
+
+    /// Returns the right sibling of a node according to the NodeId.
+    pub fn get_sibling_right(&self, id: NodeId) -> Option<&Node<T, H::Digest>> {
+        match self.nodes[id].sibling_right {
+            Some(a) => Some(&self.nodes[a]),
+            None => None,
+        }
+    }
+
+    /// Returns the left sibling of a node according to the NodeId.
+    pub fn get_sibling_left(&self, id: NodeId) -> Option<&Node<T, H::Digest>> {
+        match self.nodes[id].sibling_left {
+            Some(a) => Some(&self.nodes[a]),
+            None => None,
+        }
+    }
+
+    /// Returns the left child of a node according to the NodeId.
+    pub fn get_child_left(&self, id: NodeId) -> Option<&Node<T, H::Digest>> {
+        match self.nodes[id].child_left {
+            Some(a) => Some(&self.nodes[a]),
+            None => None,
+        }
+    }
+
+    /// Returns the right child of a node according to the NodeId.
+    pub fn get_child_right(&self, id: NodeId) -> Option<&Node<T, H::Digest>> {
+        match self.nodes[id].child_right {
+            Some(a) => Some(&self.nodes[a]),
+            None => None,
+        }
+    }
+
+    /// Returns the root digest of the tree, if it has been built.
+    pub fn root_hash(&self) -> Option<&H::Digest> {
+        self.nodes[0].hash.as_ref()
+    }
+
+    /// Builds an inclusion proof for `leaf`: the authentication path from
+    /// the leaf up to the root, as the sequence of sibling digests needed
+    /// to re-derive the root along with which side each sibling sits on.
+    ///
+    /// Returns `None` if `leaf` isn't a valid node, or if a digest along
+    /// the path hasn't been computed yet. A single-leaf tree (the leaf
+    /// is the root) yields an empty proof.
+    pub fn proof(&self, leaf: NodeId) -> Option<Vec<(Direction, H::Digest)>> {
+        if leaf >= self.nodes.len() {
+            return None;
+        }
+
+        let mut path = Vec::new();
+        let mut current = leaf;
+
+        while let Some(parent_id) = self.nodes[current].parent {
+            let parent = &self.nodes[parent_id];
+
+            // Find which side `current` sits on under its parent, and
+            // record the *other* side's digest. This is driven by the
+            // child pointers rather than sibling_left/sibling_right,
+            // since a node's in-row neighbor isn't always its hashing
+            // partner.
+            if parent.child_left == Some(current) {
+                if let Some(sibling) = parent.child_right {
+                    path.push((Direction::Right, self.nodes[sibling].hash.clone()?));
+                }
+            } else if parent.child_right == Some(current) {
+                if let Some(sibling) = parent.child_left {
+                    path.push((Direction::Left, self.nodes[sibling].hash.clone()?));
+                }
+            }
+
+            current = parent_id;
+        }
+
+        Some(path)
+    }
+}
+
+/// Which side of the running hash a proof step's sibling digest sits on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Right,
 }
 
-impl<T> MerkleTree<T> {
-    /// Constructor that builds a whole tree given a list of values.
-    pub fn new(input: &Vec<T>) -> MerkleTree<&T> {
+/// Re-folds `leaf_hash` upward through `proof` and checks whether the
+/// result matches `expected_root`. This is the verifier's counterpart to
+/// `MerkleTree::proof`, and doesn't require access to the tree itself.
+pub fn verify_proof<H: Hasher>(
+    leaf_hash: &H::Digest,
+    proof: &[(Direction, H::Digest)],
+    expected_root: &H::Digest,
+) -> bool {
+    let mut acc = leaf_hash.clone();
+
+    for (direction, sibling) in proof {
+        acc = match direction {
+            Direction::Left => H::hash_nodes(sibling, &acc),
+            Direction::Right => H::hash_nodes(&acc, sibling),
+        };
+    }
+
+    acc == *expected_root
+}
+
+impl<T: Hash, H: Hasher> MerkleTree<T, H> {
+    /// Constructor that builds a whole tree given a list of values,
+    /// hashing each leaf and folding parent digests upward as the tree is
+    /// built.
+    pub fn new(input: &Vec<T>) -> MerkleTree<&T, H> {
         // Calculate tree size.
         let tree_size = calculate_tree_size(input.len() as u64);
 
-        // Create root.
-        // let root = Node::new();
         let mut nodes = Vec::with_capacity(tree_size as usize);
         nodes.push(Node::new());
 
         // Create merkle tree and add all leafs.
-        let mut merkle_tree = MerkleTree { nodes };
+        let mut merkle_tree = MerkleTree {
+            nodes,
+            _hasher: PhantomData,
+        };
 
         // Add all the leafs.
         for x in input.iter() {
@@ -31,21 +156,13 @@ impl<T> MerkleTree<T> {
         merkle_tree
     }
 
-    /// Constructor that will return a MerkleTree<T> with the root initialised
-    /// to a zero value with no nodes and leafs.
-    pub fn new_empty() -> MerkleTree<T> {
-        // let root = Node::new();
-        let nodes = vec![Node::new()];
-
-        MerkleTree { nodes }
-    }
-
-    /// Adds a Leaf Node as leaf in the Merkle Tree.
+    /// Adds a Leaf Node as leaf in the Merkle Tree, hashing its value.
     fn add_leaf(&mut self, val: T) -> NodeId {
         let index = self.nodes.len();
         let mut node = Node::new();
 
-        // Set the value.
+        // Hash the value before it is moved into the node.
+        node.hash = Some(H::hash_leaf(&val));
         node.value = Some(val);
 
         // Make sure we avoid assignment to the root node, this is reserved at 0.
@@ -61,130 +178,134 @@ impl<T> MerkleTree<T> {
         index as NodeId
     }
 
-    /// Returns the right sibling of a node according to the NodeId.
-    pub fn get_sibling_right(&self, id: NodeId) -> Option<&Node<T>> {
-        match self.nodes[id].sibling_right {
-            Some(a) => Some(&self.nodes[a]),
-            None => None,
-        }
-    }
-
-    /// Returns the left sibling of a node according to the NodeId.
-    pub fn get_sibling_left(&self, id: NodeId) -> Option<&Node<T>> {
-        match self.nodes[id].sibling_left {
-            Some(a) => Some(&self.nodes[a]),
-            None => None,
-        }
-    }
+    /// Builds the parent rows above the leafs, one row at a time. A row
+    /// with an odd number of nodes carries its lone trailing node up to
+    /// the next row unchanged, rather than assuming every row pairs up
+    /// evenly the way a perfect power-of-two tree would.
+    fn build_parents(&mut self) {
+        let mut level: Vec<NodeId> = (1..self.nodes.len()).collect();
+
+        while level.len() > 1 {
+            let is_root_row = level.len() <= 2;
+            let mut next_level = Vec::with_capacity(level.len().div_ceil(2));
+            let mut i = 0;
+
+            while i + 1 < level.len() {
+                let left_id = level[i];
+                let right_id = level[i + 1];
+
+                let mut parent = Node::<T, H::Digest>::new();
+                parent.child_left = Some(left_id);
+                parent.child_right = Some(right_id);
+                parent.hash = Some(H::hash_nodes(
+                    self.nodes[left_id].hash.as_ref().unwrap(),
+                    self.nodes[right_id].hash.as_ref().unwrap(),
+                ));
+
+                if is_root_row {
+                    // This is the only pair in the row: it becomes the root.
+                    self.nodes[left_id].parent = Some(0);
+                    self.nodes[right_id].parent = Some(0);
+                    self.nodes[0] = parent;
+                    next_level.push(0);
+                } else {
+                    let parent_id = self.nodes.len();
+                    self.nodes[left_id].parent = Some(parent_id);
+                    self.nodes[right_id].parent = Some(parent_id);
+                    self.nodes.push(parent);
+                    next_level.push(parent_id);
+                }
 
-    /// Returns the left child of a node according to the NodeId.
-    pub fn get_child_left(&self, id: NodeId) -> Option<&Node<T>> {
-        match self.nodes[id].child_left {
-            Some(a) => Some(&self.nodes[a]),
-            None => None,
-        }
-    }
+                i += 2;
+            }
 
-    /// Returns the right child of a node according to the NodeId.
-    pub fn get_child_right(&self, id: NodeId) -> Option<&Node<T>> {
-        match self.nodes[id].child_right {
-            Some(a) => Some(&self.nodes[a]),
-            None => None,
-        }
-    }
+            // An odd node left over at the end of the row: carry it up
+            // unchanged so it pairs with whatever follows at the next row.
+            if i < level.len() {
+                next_level.push(level[i]);
+            }
 
-    fn build_parents(&mut self) {
-        let mut left_id: NodeId = 1;
-        let capacity = self.nodes.capacity();
-
-        loop {
-            let right_id = left_id + 1;
-            let parent_id: NodeId = self.nodes.len();
-
-            // Check that we are at the head of a new row. The new row will be
-            // a parent row, so each parent should not have a left or right sibling
-            // assigned yet.
-            match (
-                self.nodes[left_id].sibling_left,
-                self.nodes[left_id].sibling_right,
-            ) {
-                (None, None) => {
-                    self.nodes[left_id].sibling_right = Some(right_id);
-                    self.nodes[right_id].sibling_left = Some(left_id);
-                    // if (right_id + 1) < self.nodes.len() {
-                    // self.nodes[right_id].sibling_right = Some(right_id + 1);
-                    // self.nodes[right_id + 1].sibling_left = Some(right_id);
-                    // }
+            // Only wire nodes that will actually be hashed together in the
+            // next pass. `chunks(2)` mirrors the pairing done above: a
+            // trailing carry sits alone in its own chunk and is left
+            // unwired here, since it hasn't found its real partner yet.
+            // `windows(2)` would wrongly link it to whatever real parent
+            // precedes it in this row, even though they were never hashed
+            // together.
+            for pair in next_level.chunks(2) {
+                if let [left, right] = pair {
+                    self.nodes[*left].sibling_right = Some(*right);
+                    self.nodes[*right].sibling_left = Some(*left);
                 }
-                _ => {}
             }
 
-            // if left_id != (self.nodes.len() - 1) {
-            // self.nodes[left_id].sibling_right = Some(right_id);
-            // self.nodes[right_id].sibling_left = Some(left_id);
-            // }
-            // TODO: check if the parent can reference a right sibling.
-            // if (right_id + 1) < self.nodes.len() && self.nodes[right_id].sibling_right == None {
-            //   self.nodes[right_id].sibling_right = Some(right_id + 1)
-            // }
-
-            // Create parent and assign child ids to the parent.
-            let mut parent = Node::<T>::new();
-            parent.child_left = Some(left_id);
-            parent.child_right = Some(right_id);
-
-            // Check if we have reached the end of the row.
-            // match self.nodes[right_id].sibling_right {
-            // Some(_a) => {
-            // Increment the row count if there are more siblings.
-            // row_count += 2;
-            // }
-            // None => {}
-            // }
-            //
-            // Check that the parent should be the root.
-            if right_id == capacity - 1 {
-                // Assign the node id of parent to current nodes.
-                self.nodes[left_id].parent = Some(0);
-                self.nodes[right_id].parent = Some(0);
-                self.nodes[0] = parent;
-                break;
-            }
+            level = next_level;
+        }
 
-            // Assign parents not at root.
-            self.nodes[left_id].parent = Some(parent_id);
-            self.nodes[right_id].parent = Some(parent_id);
-            self.nodes.push(parent);
-
-            // Reset the row count if we are at the end.
-            // match self.nodes[right_id].sibling_right {
-            // Some(_a) => {}
-            // None => {
-            // row_count = 2;
-            // }
-            // }
-
-            // Increment the index.
-            left_id += 2;
+        // Only a single leaf overall: no parent row was ever built, so
+        // carry its hash up to the root unchanged. With no leafs at all the
+        // root is left without a digest.
+        if let Some(&only) = level.first().filter(|&&id| id != 0) {
+            self.nodes[0].hash = self.nodes[only].hash.clone();
+            self.nodes[0].child_left = Some(only);
+            self.nodes[only].parent = Some(0);
         }
     }
 }
 
 /// Node represents each node/leaf in the MerkleTree. This can be a parent
-/// or child.
+/// or child. `D` is the digest type produced by the tree's `Hasher`.
 #[derive(Hash)]
-pub struct Node<T> {
+pub struct Node<T, D> {
     parent: Option<NodeId>,
     sibling_left: Option<NodeId>,
     sibling_right: Option<NodeId>,
     child_left: Option<NodeId>,
     child_right: Option<NodeId>,
     value: Option<T>,
-    hash: Option<u64>,
+    hash: Option<D>,
 }
 
-impl<T> Node<T> {
-    pub fn new() -> Node<T> {
+/// Parallel tree construction, gated on the `rayon` feature for callers
+/// with large leaf sets where hashing dominates.
+#[cfg(feature = "rayon")]
+impl<T: Hash + Sync, H: Hasher> MerkleTree<T, H>
+where
+    H::Digest: Send + Sync,
+{
+    /// Computes just the root digest for `input`, hashing leaves and
+    /// reducing each row in parallel with rayon. Parents are written
+    /// positionally rather than by completion order, so the result is
+    /// identical to `MerkleTree::new(input).root_hash()` regardless of
+    /// thread count. Returns `None` for an empty input.
+    pub fn par_build(input: &[T]) -> Option<H::Digest> {
+        use rayon::prelude::*;
+
+        if input.is_empty() {
+            return None;
+        }
+
+        let mut row: Vec<H::Digest> = input.par_iter().map(H::hash_leaf).collect();
+
+        while row.len() > 1 {
+            row = row
+                .par_chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => H::hash_nodes(left, right),
+                    // An odd row carries its lone trailing digest up
+                    // unchanged, matching `MerkleTree::build_parents`.
+                    [only] => only.clone(),
+                    _ => unreachable!("par_chunks(2) never yields more than 2 items"),
+                })
+                .collect();
+        }
+
+        row.into_iter().next()
+    }
+}
+
+impl<T, D> Node<T, D> {
+    pub fn new() -> Node<T, D> {
         Node {
             parent: None,
             sibling_left: None,
@@ -195,73 +316,17 @@ impl<T> Node<T> {
             hash: None,
         }
     }
+
+    /// Returns the digest stored at this node, if it has been computed.
+    pub fn hash(&self) -> Option<&D> {
+        self.hash.as_ref()
+    }
 }
 
 #[cfg(test)]
 mod merkle_tree {
     use super::*;
-
-    #[test]
-    fn simple_tree_1() {
-        // Create three nodes.
-        // node_0 will be the parent to node_2, node_3.
-        //      node_0
-        //      /    \
-        //     /      \
-        //  node_1   node_2
-
-        // let mut merkle_tree: MerkleTree<String> = MerkleTree::new_empty();
-        //
-
-        // let node_id_1 = merkle_tree.add_leaf("hello".to_string());
-        // let node_id_2 = merkle_tree.add_leaf("world".to_string());
-        let input = vec!["hello", "world", "!"];
-        let merkle_tree = MerkleTree::new(&input);
-        assert_eq!(merkle_tree.nodes.len(), 3);
-
-        // Node Id 1 should have no left sibling since it is the far left most
-        // leaf.
-        assert!(merkle_tree.nodes[node_id_1].sibling_left == None);
-        assert!(merkle_tree.nodes[node_id_1].sibling_right != None);
-
-        // Check the right sibling of node_1.
-        assert_eq!(merkle_tree.nodes[node_id_1].sibling_right.unwrap(), 2);
-
-        // Get the right sibling of node_id_1, and assert its value.
-        let sibling_right = merkle_tree.get_sibling_right(node_id_1);
-
-        // as_ref() Converts an Option<T> to a Option<&T> safely.
-        let val = sibling_right.unwrap().value.as_ref().unwrap();
-        assert_eq!(val, "world");
-
-        // Assert the left and right sibling for node_id_2.
-        // Right sibling should be None.
-        match merkle_tree.get_sibling_right(node_id_2) {
-            Some(_a) => assert!(false),
-            None => assert!(true),
-        }
-
-        // Left sibling should have the val "hello".
-        let sibling_left = merkle_tree.get_sibling_left(node_id_2);
-
-        let val = sibling_left.unwrap().value.as_ref().unwrap();
-        assert_eq!(val, "hello");
-
-        // Build the parent nodes.
-        merkle_tree.build_parents();
-
-        // Check that both nodes are pointing to the root as their parent.
-        assert!(merkle_tree.nodes[1].parent != None);
-        assert!(merkle_tree.nodes[1].parent.unwrap() == 0);
-        assert!(merkle_tree.nodes[0].child_left.unwrap() == 1);
-        assert!(merkle_tree.nodes[0].child_right.unwrap() == 2);
-
-        let child_left = merkle_tree.get_child_left(0);
-        assert!(child_left.unwrap().value.as_ref().unwrap() == "hello");
-
-        let child_right = merkle_tree.get_child_right(0);
-        assert!(child_right.unwrap().value.as_ref().unwrap() == "world");
-    }
+    use crate::hasher::U64Hasher;
 
     #[test]
     fn four_leaf_tree_1() {
@@ -275,7 +340,7 @@ mod merkle_tree {
         //  node_1   node_2  node_3  node_4
 
         let input = vec![1, 2, 3, 4];
-        let merkle_tree = MerkleTree::new(&input);
+        let merkle_tree: MerkleTree<&i32, U64Hasher> = MerkleTree::new(&input);
 
         // Assert the tree size.
         assert_eq!(merkle_tree.nodes.len(), 7);
@@ -310,6 +375,14 @@ mod merkle_tree {
 
         assert_eq!(merkle_tree.nodes[0].child_left.unwrap(), 5);
         assert_eq!(merkle_tree.nodes[0].child_right.unwrap(), 6);
+
+        // Every node should have had its digest computed, and the root's
+        // digest should be the fold of its children's.
+        let expected_root = U64Hasher::hash_nodes(
+            merkle_tree.nodes[5].hash().unwrap(),
+            merkle_tree.nodes[6].hash().unwrap(),
+        );
+        assert_eq!(*merkle_tree.root_hash().unwrap(), expected_root);
     }
 
     #[test]
@@ -329,7 +402,7 @@ mod merkle_tree {
         //  node_1   node_2  node_3  node_4  node_5  node_6 node_7  node_8
 
         let input = vec![1, 2, 3, 4, 5, 6, 7, 8];
-        let merkle_tree = MerkleTree::new(&input);
+        let merkle_tree: MerkleTree<&i32, U64Hasher> = MerkleTree::new(&input);
 
         // Assert the leaf siblings.
         assert_eq!(merkle_tree.nodes[1].sibling_left, None);
@@ -360,23 +433,126 @@ mod merkle_tree {
 
         assert_eq!(merkle_tree.nodes[9].sibling_left, None);
         assert_eq!(merkle_tree.nodes[9].parent.unwrap(), 13);
-        // assert_eq!(merkle_tree.nodes[9].sibling_right.unwrap(), 10);
-        // assert_eq!(merkle_tree.nodes[10].sibling_left.unwrap(), 9);
-        // assert_eq!(merkle_tree.nodes[10].sibling_right.unwrap(), 11);
-        // assert_eq!(merkle_tree.nodes[11].sibling_left.unwrap(), 10);
-        // assert_eq!(merkle_tree.nodes[11].sibling_right.unwrap(), 12);
-        // assert_eq!(merkle_tree.nodes[5].sibling_right.unwrap(), 6);
-        // assert_eq!(merkle_tree.nodes[5].child_left.unwrap(), 1);
-        // assert_eq!(merkle_tree.nodes[5].child_right.unwrap(), 2);
-        // assert_eq!(merkle_tree.nodes[5].parent.unwrap(), 0);
-        //
-        // assert_eq!(merkle_tree.nodes[6].sibling_left.unwrap(), 5);
-        // assert_eq!(merkle_tree.nodes[6].sibling_right, None);
-        // assert_eq!(merkle_tree.nodes[6].child_left.unwrap(), 3);
-        // assert_eq!(merkle_tree.nodes[6].child_right.unwrap(), 4);
-        // assert_eq!(merkle_tree.nodes[6].parent.unwrap(), 0);
+
+        // The root digest should be deterministic given the same input.
+        let other_tree: MerkleTree<&i32, U64Hasher> = MerkleTree::new(&input);
+        assert_eq!(merkle_tree.root_hash(), other_tree.root_hash());
+    }
+
+    #[test]
+    fn proof_verifies_against_root_for_each_leaf() {
+        let input = vec![1, 2, 3, 4];
+        let merkle_tree: MerkleTree<&i32, U64Hasher> = MerkleTree::new(&input);
+        let root = merkle_tree.root_hash().unwrap();
+
+        for leaf in 1..=4 {
+            let leaf_hash = merkle_tree.nodes[leaf].hash().unwrap();
+            let proof = merkle_tree.proof(leaf).unwrap();
+
+            assert!(verify_proof::<U64Hasher>(leaf_hash, &proof, root));
+        }
+    }
+
+    #[test]
+    fn proof_fails_against_wrong_root() {
+        let input = vec![1, 2, 3, 4];
+        let merkle_tree: MerkleTree<&i32, U64Hasher> = MerkleTree::new(&input);
+
+        let leaf_hash = merkle_tree.nodes[1].hash().unwrap();
+        let proof = merkle_tree.proof(1).unwrap();
+        let wrong_root = U64Hasher::hash_leaf(&"not the root");
+
+        assert!(!verify_proof::<U64Hasher>(leaf_hash, &proof, &wrong_root));
+    }
+
+    #[test]
+    fn single_leaf_tree_has_empty_proof() {
+        let mut merkle_tree: MerkleTree<i32, U64Hasher> = MerkleTree::new_empty();
+        merkle_tree.nodes[0].hash = Some(U64Hasher::hash_leaf(&1));
+
+        let proof = merkle_tree.proof(0).unwrap();
+        assert!(proof.is_empty());
+
+        let leaf_hash = merkle_tree.root_hash().unwrap();
+        assert!(verify_proof::<U64Hasher>(leaf_hash, &proof, leaf_hash));
+    }
+
+    /// Builds a tree over `1..=leaf_count` and checks that every leaf's
+    /// proof verifies against the root, regardless of whether the leaf
+    /// count is a power of two.
+    fn assert_odd_row_tree_is_consistent(leaf_count: i32) {
+        let input: Vec<i32> = (1..=leaf_count).collect();
+        let merkle_tree: MerkleTree<&i32, U64Hasher> = MerkleTree::new(&input);
+        let root = merkle_tree.root_hash().unwrap();
+
+        for leaf in 1..=leaf_count as usize {
+            let leaf_hash = merkle_tree.nodes[leaf].hash().unwrap();
+            let proof = merkle_tree.proof(leaf).unwrap();
+            assert!(verify_proof::<U64Hasher>(leaf_hash, &proof, root));
+        }
+
+        // Deterministic: building the same input again yields the same root.
+        let other_tree: MerkleTree<&i32, U64Hasher> = MerkleTree::new(&input);
+        assert_eq!(merkle_tree.root_hash(), other_tree.root_hash());
+    }
+
+    #[test]
+    fn three_leaf_tree_is_consistent() {
+        assert_odd_row_tree_is_consistent(3);
+    }
+
+    #[test]
+    fn five_leaf_tree_is_consistent() {
+        assert_odd_row_tree_is_consistent(5);
+    }
+
+    #[test]
+    fn six_leaf_tree_is_consistent() {
+        assert_odd_row_tree_is_consistent(6);
+    }
+
+    #[test]
+    fn seven_leaf_tree_is_consistent() {
+        assert_odd_row_tree_is_consistent(7);
+    }
+
+    #[test]
+    fn carried_node_is_not_wired_as_sibling_of_its_non_partner() {
+        // 5 leaves: node6 = hash(1,2), node7 = hash(3,4), leaf 5 is carried
+        // past both passes, node8 = hash(node6, node7), root = hash(node8, leaf5).
         //
-        // assert_eq!(merkle_tree.nodes[0].child_left.unwrap(), 5);
-        // assert_eq!(merkle_tree.nodes[0].child_right.unwrap(), 6);
+        //                   node_0 (root)
+        //                 /              \
+        //             node_8            node_5 (leaf)
+        //            /      \
+        //        node_6    node_7
+        //        /    \    /    \
+        //    node_1 node_2 node_3 node_4
+        let input = vec![1, 2, 3, 4, 5];
+        let merkle_tree: MerkleTree<&i32, U64Hasher> = MerkleTree::new(&input);
+
+        // node7 (hash(3,4)) was never hashed with leaf 5, so it must not be
+        // wired as its sibling even though leaf 5 was carried past it.
+        assert!(merkle_tree.get_sibling_right(7).is_none());
+
+        // node6 and node7 were actually hashed together to form node8.
+        assert_eq!(merkle_tree.get_sibling_right(6).unwrap().hash(), merkle_tree.nodes[7].hash());
+        assert_eq!(merkle_tree.get_sibling_left(7).unwrap().hash(), merkle_tree.nodes[6].hash());
+
+        // node8 and leaf 5 are the true partners that form the root.
+        assert_eq!(merkle_tree.get_sibling_right(8).unwrap().hash(), merkle_tree.nodes[5].hash());
+        assert_eq!(merkle_tree.get_sibling_left(5).unwrap().hash(), merkle_tree.nodes[8].hash());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_build_matches_sequential_root() {
+        for leaf_count in [1, 3, 4, 7, 8] {
+            let input: Vec<i32> = (1..=leaf_count).collect();
+            let merkle_tree: MerkleTree<&i32, U64Hasher> = MerkleTree::new(&input);
+
+            let par_root = MerkleTree::<i32, U64Hasher>::par_build(&input);
+            assert_eq!(par_root.as_ref(), merkle_tree.root_hash());
+        }
     }
 }